@@ -0,0 +1,150 @@
+use serde_json::Value;
+
+/// The `schema_version` every freshly-serialized `SerialMonitorData` carries.
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
+
+type Migration = fn(Value) -> Value;
+
+/// Ordered migration steps. `MIGRATIONS[n]` takes a value from schema version
+/// `n` to schema version `n + 1`.
+const MIGRATIONS: &[Migration] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4
+];
+
+/// Reads `schema_version` off a raw config value (defaulting to 0 when it's
+/// absent, i.e. a save from before versioning existed) and runs it through
+/// every migration needed to reach `CURRENT_SCHEMA_VERSION`.
+pub fn migrate_to_current(value: Value) -> Value {
+    let version = value.get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as usize;
+
+    let mut migrated = MIGRATIONS.iter()
+        .skip(version)
+        .fold(value, |value, migration| migration(value));
+
+    if let Some(obj) = migrated.as_object_mut() {
+        obj.insert(String::from("schema_version"), serde_json::json!(CURRENT_SCHEMA_VERSION));
+    }
+
+    migrated
+}
+
+/// v0 saves predate `start_msg`; default it the same way `ConnectionConfig`'s
+/// `Default` impl does.
+fn migrate_v0_to_v1(mut value: Value) -> Value {
+    if let Some(conn_config) = value.get_mut("conn_config").and_then(Value::as_object_mut) {
+        conn_config.entry("start_msg").or_insert_with(|| Value::String(String::from("Start")));
+    }
+    value
+}
+
+/// v1 saves call the plot time window `window`; v2 renames it to
+/// `plot_window` so it can't be confused with `TriggerConfig::window`.
+fn migrate_v1_to_v2(mut value: Value) -> Value {
+    if let Some(plot_config) = value.get_mut("plot_config").and_then(Value::as_object_mut) {
+        if let Some(window) = plot_config.remove("window") {
+            plot_config.insert(String::from("plot_window"), window);
+        }
+    }
+    value
+}
+
+/// v2 saves predate the edge/level trigger modes; default every new
+/// `TriggerConfig` field to the same values as its `Default` impl, which
+/// reproduces the old deviation-only behavior exactly.
+fn migrate_v2_to_v3(mut value: Value) -> Value {
+    if let Some(trigger_config) = value.get_mut("trigger_config").and_then(Value::as_object_mut) {
+        trigger_config.entry("mode").or_insert_with(|| Value::String(String::from("Deviation")));
+        trigger_config.entry("trigger_level").or_insert(serde_json::json!(0.0));
+        trigger_config.entry("hysteresis").or_insert(serde_json::json!(0.05));
+        trigger_config.entry("pre_trigger").or_insert(serde_json::json!(0.05));
+        trigger_config.entry("post_trigger").or_insert(serde_json::json!(0.05));
+    }
+    value
+}
+
+/// v3 saves predate framed-binary input; everyone before this was reading
+/// plain ASCII numbers, so default the new field to that. `influx_export`
+/// is defaulted here too: it was added to `SerialMonitorData` before schema
+/// versioning existed and had no migration of its own, so a save from
+/// before v4 may or may not carry it; one that does is left unchanged.
+fn migrate_v3_to_v4(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("input_format").or_insert_with(|| Value::String(String::from("Ascii")));
+        obj.entry("influx_export").or_insert_with(default_influx_export);
+    }
+    value
+}
+
+fn default_influx_export() -> Value {
+    serde_json::json!({
+        "enabled": false,
+        "measurement": "serialmonitor",
+        "batch_size": 64,
+        "sink": { "File": "capture.lp" }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_v0_to_v1_defaults_start_msg() {
+        let value = serde_json::json!({"conn_config": {}});
+        let migrated = migrate_v0_to_v1(value);
+        assert_eq!(migrated["conn_config"]["start_msg"], "Start");
+    }
+
+    #[test]
+    fn migrate_v1_to_v2_renames_plot_window() {
+        let value = serde_json::json!({"plot_config": {"window": 5.0}});
+        let migrated = migrate_v1_to_v2(value);
+        assert_eq!(migrated["plot_config"]["plot_window"], 5.0);
+        assert!(migrated["plot_config"].get("window").is_none());
+    }
+
+    #[test]
+    fn migrate_v2_to_v3_defaults_trigger_fields() {
+        let value = serde_json::json!({"trigger_config": {"window": 0.05, "tolerance": 0.1, "input_slot": 0}});
+        let migrated = migrate_v2_to_v3(value);
+        assert_eq!(migrated["trigger_config"]["mode"], "Deviation");
+        assert_eq!(migrated["trigger_config"]["trigger_level"], 0.0);
+        assert_eq!(migrated["trigger_config"]["hysteresis"], 0.05);
+        assert_eq!(migrated["trigger_config"]["pre_trigger"], 0.05);
+        assert_eq!(migrated["trigger_config"]["post_trigger"], 0.05);
+    }
+
+    #[test]
+    fn migrate_v3_to_v4_defaults_input_format_and_influx_export() {
+        let value = serde_json::json!({});
+        let migrated = migrate_v3_to_v4(value);
+        assert_eq!(migrated["input_format"], "Ascii");
+        assert_eq!(migrated["influx_export"]["enabled"], false);
+        assert_eq!(migrated["influx_export"]["sink"]["File"], "capture.lp");
+    }
+
+    #[test]
+    fn migrate_to_current_round_trips_a_v0_save() {
+        let v0 = serde_json::json!({
+            "conn_config": {},
+            "plot_config": {"window": 5.0},
+            "trigger_config": {"window": 0.05, "tolerance": 0.1, "input_slot": 0},
+            "inp_slots": [],
+            "plots": []
+        });
+
+        let migrated = migrate_to_current(v0);
+
+        assert_eq!(migrated["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert_eq!(migrated["conn_config"]["start_msg"], "Start");
+        assert_eq!(migrated["plot_config"]["plot_window"], 5.0);
+        assert_eq!(migrated["trigger_config"]["mode"], "Deviation");
+        assert_eq!(migrated["input_format"], "Ascii");
+        assert_eq!(migrated["influx_export"]["enabled"], false);
+    }
+}
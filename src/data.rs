@@ -1,3 +1,6 @@
+use crate::config_migration;
+use crate::influx_export::InfluxExportConfig;
+use crate::packet_framing::PacketFormat;
 use crate::serial_reader::{FlowCtrl, Parity, SerialConfig, StartMode};
 use std::{fmt::Display, fs::File, io::Write, path::PathBuf, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
 use serde::{Serialize, Deserialize};
@@ -61,6 +64,18 @@ impl ConnectionConfig {
     pub const NO_PORT: &'static str = "-";
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub enum InputFormat {
+    Ascii,
+    Framed(PacketFormat)
+}
+
+impl Default for InputFormat {
+    fn default() -> Self {
+        Self::Ascii
+    }
+}
+
 #[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PlotMode {
     Continous,
@@ -90,7 +105,7 @@ impl Display for PlotScaleMode {
 #[derive(Serialize, Deserialize)]
 pub struct PlotConfig {
     pub mode: PlotMode,
-    pub window: f64,
+    pub plot_window: f64,
     pub scale_mode: PlotScaleMode,
     pub y_min: f64,
     pub y_max: f64
@@ -98,9 +113,9 @@ pub struct PlotConfig {
 
 impl Default for PlotConfig {
     fn default() -> Self {
-        Self { 
+        Self {
             mode: PlotMode::Continous,
-            window: 5.0,
+            plot_window: 5.0,
             scale_mode: PlotScaleMode::Auto,
             y_min: 0.0,
             y_max: 1.0
@@ -108,19 +123,43 @@ impl Default for PlotConfig {
     }
 }
 
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TriggerMode {
+    Deviation,
+    RisingEdge,
+    FallingEdge,
+    Level
+}
+
+impl Display for TriggerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TriggerConfig {
+    pub mode: TriggerMode,
     pub window: f64,
     pub tolerance: f64,
     pub input_slot: usize,
+    pub trigger_level: f64,
+    pub hysteresis: f64,
+    pub pre_trigger: f64,
+    pub post_trigger: f64
 }
 
 impl Default for TriggerConfig {
     fn default() -> Self {
-        Self { 
+        Self {
+            mode: TriggerMode::Deviation,
             window: 0.05,
             tolerance: 0.1,
-            input_slot: 0
+            input_slot: 0,
+            trigger_level: 0.0,
+            hysteresis: 0.05,
+            pre_trigger: 0.05,
+            post_trigger: 0.05
         }
     }
 }
@@ -175,16 +214,27 @@ impl PlotData {
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct SerialMonitorData {
+    #[serde(default)]
+    pub schema_version: u32,
     pub conn_config: ConnectionConfig,
     pub plot_config: PlotConfig,
     pub trigger_config: TriggerConfig,
     pub inp_slots: Vec<InputSlot>,
-    pub plots: Vec<PlotData>
+    pub plots: Vec<PlotData>,
+    pub influx_export: InfluxExportConfig,
+    pub input_format: InputFormat
 }
 
 impl SerialMonitorData {
+    pub const CURRENT_SCHEMA_VERSION: u32 = config_migration::CURRENT_SCHEMA_VERSION;
+
     pub fn serialize(path: &PathBuf, data: &SerialMonitorData) -> std::io::Result<()> {
-        let config = serde_json::to_string_pretty(data)
+        let mut data_with_version = serde_json::to_value(data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        if let Some(obj) = data_with_version.as_object_mut() {
+            obj.insert(String::from("schema_version"), serde_json::json!(Self::CURRENT_SCHEMA_VERSION));
+        }
+        let config = serde_json::to_string_pretty(&data_with_version)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
         let mut file = File::create(&path)?;
         file.write_all(config.as_bytes())?;
@@ -193,19 +243,31 @@ impl SerialMonitorData {
 
     pub fn deserialize(path: &PathBuf) -> Result<SerialMonitorData, std::io::Error> {
         let file = File::open(path)?;
-        let config: SerialMonitorData = serde_json::from_reader(&file)
+        let value: serde_json::Value = serde_json::from_reader(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let migrated = config_migration::migrate_to_current(value);
+        let config: SerialMonitorData = serde_json::from_value(migrated)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
         Ok(config)
     }
 }
 
 pub fn detect_single_shot(values: &Vec<Vec<[f64; 2]>>, trigger_config: &TriggerConfig) -> Option<(f64, f64)> {
+    match trigger_config.mode {
+        TriggerMode::Deviation => detect_deviation(values, trigger_config),
+        TriggerMode::RisingEdge => detect_edge(values, trigger_config, true),
+        TriggerMode::FallingEdge => detect_edge(values, trigger_config, false),
+        TriggerMode::Level => detect_level(values, trigger_config)
+    }
+}
+
+fn detect_deviation(values: &Vec<Vec<[f64; 2]>>, trigger_config: &TriggerConfig) -> Option<(f64, f64)> {
     let window = trigger_config.window;
     let tolerance = trigger_config.tolerance;
     let input_slot = trigger_config.input_slot;
 
     if input_slot >= values.len() || values[input_slot].len() < 2 {
-        return None;        
+        return None;
     }
 
     let values = &values[input_slot];
@@ -249,3 +311,134 @@ pub fn detect_single_shot(values: &Vec<Vec<[f64; 2]>>, trigger_config: &TriggerC
 
     last_event
 }
+
+/// Rising/falling edge detector with hysteresis: the signal must first move
+/// past `trigger_level` on the opposite side by at least `hysteresis` before
+/// a crossing back over `trigger_level` is allowed to fire, which keeps noise
+/// sitting near the threshold from re-triggering on every sample.
+fn detect_edge(values: &Vec<Vec<[f64; 2]>>, trigger_config: &TriggerConfig, rising: bool) -> Option<(f64, f64)> {
+    let input_slot = trigger_config.input_slot;
+
+    if input_slot >= values.len() || values[input_slot].len() < 2 {
+        return None;
+    }
+
+    let values = &values[input_slot];
+    let level = trigger_config.trigger_level;
+    let hysteresis = trigger_config.hysteresis;
+
+    let mut armed = false;
+
+    for pair in values.windows(2) {
+        let (t0, v0) = (pair[0][0], pair[0][1]);
+        let (t1, v1) = (pair[1][0], pair[1][1]);
+
+        if rising {
+            if v0 < level - hysteresis {
+                armed = true;
+            }
+            if armed && v0 < level && v1 >= level {
+                let t_trigger = interpolate_crossing(t0, v0, t1, v1, level);
+                return Some((t_trigger - trigger_config.pre_trigger, t_trigger + trigger_config.post_trigger));
+            }
+        } else {
+            if v0 > level + hysteresis {
+                armed = true;
+            }
+            if armed && v0 > level && v1 <= level {
+                let t_trigger = interpolate_crossing(t0, v0, t1, v1, level);
+                return Some((t_trigger - trigger_config.pre_trigger, t_trigger + trigger_config.post_trigger));
+            }
+        }
+    }
+
+    None
+}
+
+/// Fires on whichever direction - rising or falling - crosses `trigger_level`
+/// first chronologically, each side gated by its own hysteresis the same way
+/// `detect_edge` gates a single direction.
+fn detect_level(values: &Vec<Vec<[f64; 2]>>, trigger_config: &TriggerConfig) -> Option<(f64, f64)> {
+    let input_slot = trigger_config.input_slot;
+
+    if input_slot >= values.len() || values[input_slot].len() < 2 {
+        return None;
+    }
+
+    let values = &values[input_slot];
+    let level = trigger_config.trigger_level;
+    let hysteresis = trigger_config.hysteresis;
+
+    let mut armed_rising = false;
+    let mut armed_falling = false;
+
+    for pair in values.windows(2) {
+        let (t0, v0) = (pair[0][0], pair[0][1]);
+        let (t1, v1) = (pair[1][0], pair[1][1]);
+
+        if v0 < level - hysteresis {
+            armed_rising = true;
+        }
+        if v0 > level + hysteresis {
+            armed_falling = true;
+        }
+
+        if (armed_rising && v0 < level && v1 >= level) || (armed_falling && v0 > level && v1 <= level) {
+            let t_trigger = interpolate_crossing(t0, v0, t1, v1, level);
+            return Some((t_trigger - trigger_config.pre_trigger, t_trigger + trigger_config.post_trigger));
+        }
+    }
+
+    None
+}
+
+fn interpolate_crossing(t0: f64, v0: f64, t1: f64, v1: f64, level: f64) -> f64 {
+    if (v1 - v0).abs() < f64::EPSILON {
+        return t0;
+    }
+    t0 + (level - v0) * (t1 - t0) / (v1 - v0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_trigger_config() -> TriggerConfig {
+        TriggerConfig { trigger_level: 0.0, hysteresis: 0.1, pre_trigger: 0.0, post_trigger: 0.0, ..Default::default() }
+    }
+
+    #[test]
+    fn interpolate_crossing_finds_the_midpoint_for_a_linear_ramp() {
+        assert_eq!(interpolate_crossing(0.0, -1.0, 2.0, 1.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn detect_edge_fires_after_arming_past_the_hysteresis_band() {
+        let values = vec![vec![[0.0, -1.0], [1.0, -1.0], [2.0, 1.0]]];
+        let trigger_config = edge_trigger_config();
+
+        let result = detect_edge(&values, &trigger_config, true);
+
+        assert_eq!(result, Some((1.5, 1.5)));
+    }
+
+    #[test]
+    fn detect_edge_does_not_fire_without_first_arming() {
+        let values = vec![vec![[0.0, 0.05], [1.0, -0.05], [2.0, 0.05]]];
+        let trigger_config = edge_trigger_config();
+
+        assert_eq!(detect_edge(&values, &trigger_config, true), None);
+    }
+
+    #[test]
+    fn detect_level_returns_the_chronologically_first_crossing() {
+        // A falling crossing at t~0.5 happens before a rising crossing the
+        // old rising-then-falling scan would have returned instead.
+        let values = vec![vec![[0.0, 0.2], [1.0, -0.2], [2.0, -0.2], [3.0, 0.2]]];
+        let trigger_config = edge_trigger_config();
+
+        let result = detect_level(&values, &trigger_config);
+
+        assert_eq!(result, Some((0.5, 0.5)));
+    }
+}
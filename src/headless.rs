@@ -0,0 +1,185 @@
+use crate::data::{detect_single_shot, InputFormat, InputSlot, PlotMode, SerialMonitorData};
+use crate::influx_export::InfluxExporter;
+use crate::packet_framing::FrameDecoder;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc::{self, Receiver, Sender}, Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH}
+};
+
+/// Control lines a subscriber can send back over its TCP connection.
+pub enum ControlCommand {
+    Start,
+    Stop,
+    TriggerSingle
+}
+
+fn parse_control_line(line: &str) -> Option<ControlCommand> {
+    match line.trim() {
+        "start" => Some(ControlCommand::Start),
+        "stop" => Some(ControlCommand::Stop),
+        "trigger single" => Some(ControlCommand::TriggerSingle),
+        _ => None
+    }
+}
+
+/// Streams samples to every connected subscriber and relays control lines
+/// read off any subscriber's connection back to the capture loop, replying
+/// on that same connection.
+pub struct HeadlessServer {
+    subscribers: Arc<Mutex<Vec<TcpStream>>>
+}
+
+impl HeadlessServer {
+    pub fn bind(addr: &str, control_tx: Sender<(ControlCommand, TcpStream)>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let subscribers: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let accept_subscribers = subscribers.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let Ok(reader_stream) = stream.try_clone() else { continue };
+
+                accept_subscribers.lock().unwrap().push(stream);
+
+                let tx = control_tx.clone();
+                thread::spawn(move || Self::read_control_lines(reader_stream, tx));
+            }
+        });
+
+        Ok(Self { subscribers })
+    }
+
+    fn read_control_lines(stream: TcpStream, control_tx: Sender<(ControlCommand, TcpStream)>) {
+        let Ok(reply_stream) = stream.try_clone() else { return };
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let Some(command) = parse_control_line(&line) else { continue };
+            let Ok(reply) = reply_stream.try_clone() else { break };
+            if control_tx.send((command, reply)).is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn broadcast_sample(&self, timestamp: f64, inp_slots: &[InputSlot]) {
+        let mut line = timestamp.to_string();
+        for slot in inp_slots {
+            line.push(' ');
+            line.push_str(&slot.value.to_string());
+        }
+        line.push('\n');
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain_mut(|stream| stream.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+/// Decodes one read's worth of serial bytes into `inp_slots`, dispatching on
+/// `input_format` the same way the GUI's reader does.
+fn decode_samples(bytes: &[u8], input_format: &InputFormat, framed_decoder: &mut Option<FrameDecoder>, inp_slots: &mut [InputSlot]) {
+    match (input_format, framed_decoder) {
+        (InputFormat::Framed(_), Some(decoder)) => decoder.feed(bytes, inp_slots),
+        _ => decode_ascii(bytes, inp_slots)
+    }
+}
+
+/// Plain-ASCII input: one whitespace-separated number per `InputSlot`, in
+/// slot order.
+fn decode_ascii(bytes: &[u8], inp_slots: &mut [InputSlot]) {
+    let Ok(text) = std::str::from_utf8(bytes) else { return };
+    for (slot, token) in inp_slots.iter_mut().zip(text.split_whitespace()) {
+        if let Ok(value) = token.parse() {
+            slot.value = value;
+        }
+    }
+}
+
+/// Runs the serial reader and trigger engine without the GUI, broadcasting
+/// every sample to `addr` and answering `start`/`stop`/`trigger single`
+/// control lines from subscribers, including the captured pre/post-trigger
+/// window for `trigger single`.
+pub fn run_headless(mut data: SerialMonitorData, addr: &str) -> std::io::Result<()> {
+    let mut port = crate::serial_reader::open(data.conn_config.clone().into())?;
+
+    let (control_tx, control_rx): (Sender<(ControlCommand, TcpStream)>, Receiver<_>) = mpsc::channel();
+    let server = HeadlessServer::bind(addr, control_tx)?;
+
+    let mut framed_decoder = match &data.input_format {
+        InputFormat::Framed(format) => Some(FrameDecoder::new(format.clone())),
+        InputFormat::Ascii => None
+    };
+
+    let start = Instant::now();
+    let start_epoch_nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0);
+    let plot_name = data.plots.first().map(|plot| plot.name.clone()).unwrap_or_else(|| String::from("headless"));
+    let exporter = InfluxExporter::spawn(data.influx_export.clone());
+
+    let mut history: Vec<Vec<[f64; 2]>> = vec![Vec::new(); data.inp_slots.len()];
+    let mut running = false;
+    let mut read_buf = [0u8; 256];
+
+    loop {
+        while let Ok((command, mut reply)) = control_rx.try_recv() {
+            match command {
+                ControlCommand::Start => running = true,
+                ControlCommand::Stop => running = false,
+                ControlCommand::TriggerSingle => {
+                    data.plot_config.mode = PlotMode::SingleShot;
+                    let response = match detect_single_shot(&history, &data.trigger_config) {
+                        Some((pre, post)) => {
+                            // Only samples after this trigger's post-trigger
+                            // time are eligible for the next arm, otherwise
+                            // every later `trigger single` would keep finding
+                            // this same crossing.
+                            for slot_history in &mut history {
+                                slot_history.retain(|point| point[0] > post);
+                            }
+                            format!("trigger {pre} {post}\n")
+                        },
+                        None => String::from("trigger none\n")
+                    };
+                    let _ = reply.write_all(response.as_bytes());
+                }
+            }
+        }
+
+        if !running {
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        }
+
+        let n = match port.read(&mut read_buf) {
+            Ok(n) => n,
+            // `SerialConfig::timeout` is zero, so the port read is
+            // non-blocking and "nothing to read yet" surfaces as an error
+            // rather than `Ok(0)` on at least some backends; treat it the
+            // same as no data rather than tearing the server down.
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => 0,
+            Err(e) => return Err(e)
+        };
+
+        if n == 0 {
+            thread::sleep(Duration::from_millis(5));
+            continue;
+        }
+
+        decode_samples(&read_buf[..n], &data.input_format, &mut framed_decoder, &mut data.inp_slots);
+
+        let timestamp = start.elapsed().as_secs_f64();
+        let epoch_nanos = start_epoch_nanos + (timestamp * 1_000_000_000.0).round() as i64;
+
+        for (slot_index, slot) in data.inp_slots.iter().enumerate() {
+            history[slot_index].push([timestamp, slot.value]);
+            exporter.export_point(&data.influx_export, &plot_name, slot, slot.value, epoch_nanos);
+        }
+
+        server.broadcast_sample(timestamp, &data.inp_slots);
+    }
+}
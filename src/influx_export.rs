@@ -0,0 +1,180 @@
+use crate::data::InputSlot;
+use serde::{Serialize, Deserialize};
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    net::TcpStream,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::{self, JoinHandle}
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ExportSink {
+    File(PathBuf),
+    Http { url: String }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InfluxExportConfig {
+    pub enabled: bool,
+    pub measurement: String,
+    pub batch_size: usize,
+    pub sink: ExportSink
+}
+
+impl Default for InfluxExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            measurement: String::from("serialmonitor"),
+            batch_size: 64,
+            sink: ExportSink::File(PathBuf::from("capture.lp"))
+        }
+    }
+}
+
+enum ExportMsg {
+    Line(String),
+    Flush
+}
+
+/// Batches samples as InfluxDB line protocol and flushes them from a
+/// background writer thread.
+pub struct InfluxExporter {
+    tx: Option<SyncSender<ExportMsg>>,
+    worker: Option<JoinHandle<()>>
+}
+
+impl InfluxExporter {
+    pub fn spawn(config: InfluxExportConfig) -> Self {
+        let (tx, rx) = mpsc::sync_channel(4096);
+        let worker = thread::spawn(move || Self::run(config, rx));
+        Self { tx: Some(tx), worker: Some(worker) }
+    }
+
+    /// Encodes one captured sample as a line-protocol point, with `epoch_nanos`
+    /// as the point's timestamp, and hands it to the writer thread. Drops the
+    /// point instead of blocking if the sink is falling behind.
+    pub fn export_point(&self, config: &InfluxExportConfig, plot_name: &str, slot: &InputSlot, value: f64, epoch_nanos: i64) {
+        if !config.enabled {
+            return;
+        }
+
+        let line = format!(
+            "{},plot={} {}={} {}",
+            escape_tag_value(&config.measurement),
+            escape_tag_value(plot_name),
+            escape_key(&slot.name),
+            value,
+            epoch_nanos
+        );
+
+        let Some(tx) = &self.tx else { return };
+        if let Err(TrySendError::Full(_)) = tx.try_send(ExportMsg::Line(line)) {
+            // Sink is behind; drop this point rather than stall serial reading.
+        }
+    }
+
+    fn run(config: InfluxExportConfig, rx: Receiver<ExportMsg>) {
+        let mut buffer: Vec<String> = Vec::with_capacity(config.batch_size);
+
+        while let Ok(msg) = rx.recv() {
+            match msg {
+                ExportMsg::Line(line) => {
+                    buffer.push(line);
+                    if buffer.len() >= config.batch_size {
+                        Self::flush_batch(&config, &mut buffer);
+                    }
+                },
+                ExportMsg::Flush => Self::flush_batch(&config, &mut buffer)
+            }
+        }
+
+        Self::flush_batch(&config, &mut buffer);
+    }
+
+    fn flush_batch(config: &InfluxExportConfig, buffer: &mut Vec<String>) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let body = buffer.join("\n") + "\n";
+        let result = match &config.sink {
+            ExportSink::File(path) => write_to_file(path, &body),
+            ExportSink::Http { url } => write_to_http(url, &body)
+        };
+
+        if let Err(e) = result {
+            eprintln!("influx export: failed to flush {} point(s): {e}", buffer.len());
+        }
+
+        buffer.clear();
+    }
+}
+
+impl Drop for InfluxExporter {
+    fn drop(&mut self) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(ExportMsg::Flush);
+        }
+        // Drop the sender before joining: the worker's `rx.recv()` loop only
+        // exits once every sender is gone, so joining first would deadlock.
+        self.tx.take();
+        if let Some(handle) = self.worker.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn write_to_file(path: &PathBuf, body: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(body.as_bytes())
+}
+
+fn write_to_http(url: &str, body: &str) -> io::Result<()> {
+    let (host, path) = split_url(url)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("not a valid http url: {url}")))?;
+
+    let mut stream = TcpStream::connect(&host)?;
+    let request = format!(
+        "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes())
+}
+
+fn split_url(url: &str) -> Option<(String, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = if host.contains(':') { host.to_owned() } else { format!("{host}:80") };
+    Some((host, format!("/{path}")))
+}
+
+fn escape_tag_value(value: &str) -> String {
+    value.replace(' ', "\\ ").replace(',', "\\,").replace('=', "\\=")
+}
+
+fn escape_key(value: &str) -> String {
+    value.replace(' ', "_").replace(',', "_").replace('=', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_tag_value_escapes_spaces_commas_and_equals() {
+        assert_eq!(escape_tag_value("plot a, b=1"), "plot\\ a\\,\\ b\\=1");
+    }
+
+    #[test]
+    fn escape_tag_value_leaves_plain_text_alone() {
+        assert_eq!(escape_tag_value("plot1"), "plot1");
+    }
+
+    #[test]
+    fn escape_key_replaces_spaces_commas_and_equals_with_underscores() {
+        assert_eq!(escape_key("ch 1, v=x"), "ch_1__v_x");
+    }
+}
@@ -0,0 +1,233 @@
+use crate::data::InputSlot;
+use serde::{Serialize, Deserialize};
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Endian {
+    Little,
+    Big
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FieldType {
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32
+}
+
+impl FieldType {
+    fn size(self) -> usize {
+        match self {
+            FieldType::U8 => 1,
+            FieldType::I16 | FieldType::U16 => 2,
+            FieldType::I32 | FieldType::U32 | FieldType::F32 => 4
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ChecksumKind {
+    None,
+    Xor,
+    Sum8
+}
+
+impl ChecksumKind {
+    fn size(self) -> usize {
+        match self {
+            ChecksumKind::None => 0,
+            ChecksumKind::Xor | ChecksumKind::Sum8 => 1
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PacketField {
+    pub slot_index: usize,
+    pub ty: FieldType,
+    pub endian: Endian
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PacketFormat {
+    pub sync: Vec<u8>,
+    pub fields: Vec<PacketField>,
+    pub checksum: ChecksumKind
+}
+
+impl PacketFormat {
+    fn frame_len(&self) -> usize {
+        self.sync.len()
+            + self.fields.iter().map(|field| field.ty.size()).sum::<usize>()
+            + self.checksum.size()
+    }
+}
+
+impl Default for PacketFormat {
+    fn default() -> Self {
+        Self { sync: vec![0xAA, 0x55], fields: Vec::new(), checksum: ChecksumKind::Xor }
+    }
+}
+
+/// Resynchronizing frame decoder: finds `PacketFormat::sync` in the incoming
+/// byte stream and writes decoded fields onto the matching `InputSlot`s.
+#[derive(Default)]
+pub struct FrameDecoder {
+    format: PacketFormat,
+    buffer: Vec<u8>
+}
+
+impl FrameDecoder {
+    pub fn new(format: PacketFormat) -> Self {
+        Self { format, buffer: Vec::new() }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8], slots: &mut [InputSlot]) {
+        self.buffer.extend_from_slice(bytes);
+
+        // A zero-length frame (empty sync, no fields, no checksum) can never
+        // be validated or drained, which would spin the loop below forever.
+        if self.format.frame_len() == 0 {
+            self.buffer.clear();
+            return;
+        }
+
+        loop {
+            let Some(start) = find_sync(&self.buffer, &self.format.sync) else {
+                // No match yet, but the tail of the buffer may still be the
+                // leading bytes of a sync pattern that hasn't fully arrived.
+                // Keep up to sync.len() - 1 bytes so it isn't discarded out
+                // from under a split sync pattern.
+                let keep_from = self.buffer.len().saturating_sub(self.format.sync.len().saturating_sub(1));
+                self.buffer.drain(0..keep_from);
+                return;
+            };
+
+            if start > 0 {
+                self.buffer.drain(0..start);
+            }
+
+            let frame_len = self.format.frame_len();
+            if self.buffer.len() < frame_len {
+                return;
+            }
+
+            let frame = self.buffer[..frame_len].to_vec();
+            if validate_checksum(&frame, &self.format) {
+                apply_fields(&frame, &self.format, slots);
+                self.buffer.drain(0..frame_len);
+            } else {
+                self.buffer.drain(0..self.format.sync.len());
+            }
+        }
+    }
+}
+
+fn find_sync(buffer: &[u8], sync: &[u8]) -> Option<usize> {
+    if sync.is_empty() {
+        return Some(0);
+    }
+    buffer.windows(sync.len()).position(|window| window == sync)
+}
+
+fn validate_checksum(frame: &[u8], format: &PacketFormat) -> bool {
+    match format.checksum {
+        ChecksumKind::None => true,
+        ChecksumKind::Xor => {
+            let (payload, check) = frame.split_at(frame.len() - 1);
+            payload.iter().fold(0u8, |acc, b| acc ^ b) == check[0]
+        },
+        ChecksumKind::Sum8 => {
+            let (payload, check) = frame.split_at(frame.len() - 1);
+            payload.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)) == check[0]
+        }
+    }
+}
+
+fn apply_fields(frame: &[u8], format: &PacketFormat, slots: &mut [InputSlot]) {
+    let mut offset = format.sync.len();
+
+    for field in &format.fields {
+        let size = field.ty.size();
+        let bytes = &frame[offset..offset + size];
+        if let Some(slot) = slots.get_mut(field.slot_index) {
+            slot.value = decode_field(bytes, field.ty, field.endian);
+        }
+        offset += size;
+    }
+}
+
+fn decode_field(bytes: &[u8], ty: FieldType, endian: Endian) -> f64 {
+    match (ty, endian) {
+        (FieldType::U8, _) => bytes[0] as f64,
+        (FieldType::I16, Endian::Little) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        (FieldType::I16, Endian::Big) => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        (FieldType::U16, Endian::Little) => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        (FieldType::U16, Endian::Big) => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        (FieldType::I32, Endian::Little) => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        (FieldType::I32, Endian::Big) => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        (FieldType::U32, Endian::Little) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        (FieldType::U32, Endian::Big) => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        (FieldType::F32, Endian::Little) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        (FieldType::F32, Endian::Big) => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_format() -> PacketFormat {
+        PacketFormat {
+            sync: vec![0xAA, 0x55],
+            fields: vec![PacketField { slot_index: 0, ty: FieldType::U8, endian: Endian::Little }],
+            checksum: ChecksumKind::Xor
+        }
+    }
+
+    fn test_slots() -> Vec<InputSlot> {
+        vec![InputSlot::default()]
+    }
+
+    fn good_frame() -> Vec<u8> {
+        // sync, payload byte, xor checksum over sync + payload
+        vec![0xAA, 0x55, 0x2A, 0xAA ^ 0x55 ^ 0x2A]
+    }
+
+    #[test]
+    fn feed_decodes_a_frame_split_across_two_calls() {
+        let mut decoder = FrameDecoder::new(test_format());
+        let mut slots = test_slots();
+        let frame = good_frame();
+
+        decoder.feed(&frame[..1], &mut slots);
+        assert_eq!(slots[0].value, 0.0);
+
+        decoder.feed(&frame[1..], &mut slots);
+        assert_eq!(slots[0].value, 0x2A as f64);
+    }
+
+    #[test]
+    fn feed_resyncs_after_a_bad_checksum() {
+        let mut decoder = FrameDecoder::new(test_format());
+        let mut slots = test_slots();
+
+        let mut bytes = vec![0xAA, 0x55, 0x2A, 0x00]; // wrong checksum byte
+        bytes.extend(good_frame());
+
+        decoder.feed(&bytes, &mut slots);
+
+        assert_eq!(slots[0].value, 0x2A as f64);
+    }
+
+    #[test]
+    fn feed_does_not_hang_on_a_zero_length_frame() {
+        let format = PacketFormat { sync: Vec::new(), fields: Vec::new(), checksum: ChecksumKind::None };
+        let mut decoder = FrameDecoder::new(format);
+        let mut slots = test_slots();
+
+        decoder.feed(&[1, 2, 3], &mut slots);
+    }
+}